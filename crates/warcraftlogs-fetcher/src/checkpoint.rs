@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::LogEntry;
+
+/// On-disk crawl state, written after every page so a crash or Ctrl-C loses
+/// at most the in-flight pages instead of the whole crawl.
+///
+/// `zone`/`start`/`end` record the parameters the checkpoint was produced
+/// with, so resuming with a different zone or date window can be rejected
+/// instead of silently merging mismatched logs into the new run.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub zone: u32,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub next_page: usize,
+    pub matched_logs: Vec<LogEntry>,
+    pub oldest_date: Option<NaiveDate>,
+}
+
+impl Checkpoint {
+    /// Returns an error if this checkpoint was produced for a different
+    /// zone or date window than the one currently being crawled.
+    pub fn ensure_matches(&self, zone: u32, start: NaiveDate, end: NaiveDate) -> Result<()> {
+        if self.zone != zone || self.start != start || self.end != end {
+            anyhow::bail!(
+                "Checkpoint was recorded for zone {} over {}..{}, but this run is zone {} over {}..{}. \
+                 Use a different --resume file or matching --zone/--start/--end.",
+                self.zone, self.start, self.end, zone, start, end
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Loads a checkpoint from `path`, returning `None` if it doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read checkpoint {}", path.display()))?;
+        let checkpoint = serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse checkpoint {}", path.display()))?;
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes this checkpoint to `path`, overwriting whatever was there before.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(path, data)
+            .with_context(|| format!("Failed to write checkpoint {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_checkpoint() -> Checkpoint {
+        Checkpoint {
+            zone: 1006,
+            start: NaiveDate::parse_from_str("2021-05-18", "%Y-%m-%d").unwrap(),
+            end: NaiveDate::parse_from_str("2021-06-01", "%Y-%m-%d").unwrap(),
+            next_page: 3,
+            matched_logs: vec![LogEntry {
+                title: "Naxxramas Clear".to_string(),
+                date: NaiveDate::parse_from_str("2021-05-20", "%Y-%m-%d").unwrap(),
+                url: "https://classic.warcraftlogs.com/reports/abc123".to_string(),
+            }],
+            oldest_date: Some(NaiveDate::parse_from_str("2021-05-20", "%Y-%m-%d").unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "warcraftlogs-fetcher-checkpoint-test-{}.json",
+            std::process::id()
+        ));
+        let checkpoint = sample_checkpoint();
+
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap().expect("checkpoint should exist");
+
+        assert_eq!(loaded.zone, checkpoint.zone);
+        assert_eq!(loaded.start, checkpoint.start);
+        assert_eq!(loaded.end, checkpoint.end);
+        assert_eq!(loaded.next_page, checkpoint.next_page);
+        assert_eq!(loaded.matched_logs.len(), checkpoint.matched_logs.len());
+        assert_eq!(loaded.oldest_date, checkpoint.oldest_date);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_missing_file_returns_none() {
+        let path = std::env::temp_dir().join(format!(
+            "warcraftlogs-fetcher-checkpoint-missing-{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        assert!(Checkpoint::load(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_ensure_matches_rejects_different_zone() {
+        let checkpoint = sample_checkpoint();
+        assert!(checkpoint
+            .ensure_matches(1002, checkpoint.start, checkpoint.end)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ensure_matches_rejects_different_window() {
+        let checkpoint = sample_checkpoint();
+        let other_end = NaiveDate::parse_from_str("2021-07-01", "%Y-%m-%d").unwrap();
+        assert!(checkpoint
+            .ensure_matches(checkpoint.zone, checkpoint.start, other_end)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ensure_matches_accepts_same_params() {
+        let checkpoint = sample_checkpoint();
+        assert!(checkpoint
+            .ensure_matches(checkpoint.zone, checkpoint.start, checkpoint.end)
+            .is_ok());
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use dialoguer::{Confirm, Input, Select};
+
+use crate::output::OutputMode;
+use crate::{Config, SortOrder, DEFAULT_MAX_PAGES, DEFAULT_RATE_LIMIT_MS, TBC_PREPATCH_END, TBC_PREPATCH_START};
+
+/// Known zones offered in interactive mode, newest first.
+const ZONES: &[(&str, u32)] = &[
+    ("Naxxramas", 1006),
+    ("Ahn'Qiraj", 1005),
+    ("Blackwing Lair", 1002),
+    ("Molten Core", 1000),
+];
+
+/// Named date windows offered alongside a custom range option.
+const PRESETS: &[(&str, &str, &str)] = &[
+    ("TBC Pre-patch", TBC_PREPATCH_START, TBC_PREPATCH_END),
+    ("Classic launch", "2019-08-27", "2019-09-10"),
+];
+
+/// Prompts the user for a zone, date window, and rate limit, then builds a
+/// `Config` that flows into the same crawl path as one parsed from flags.
+pub fn prompt_config() -> Result<Config> {
+    let zone_names: Vec<&str> = ZONES.iter().map(|(name, _)| *name).collect();
+    let zone_idx = Select::new()
+        .with_prompt("Zone to search")
+        .items(&zone_names)
+        .default(0)
+        .interact()?;
+    let zone = ZONES[zone_idx].1;
+
+    let mut period_names: Vec<&str> = PRESETS.iter().map(|(name, _, _)| *name).collect();
+    period_names.push("Custom range");
+    let period_idx = Select::new()
+        .with_prompt("Period")
+        .items(&period_names)
+        .default(0)
+        .interact()?;
+
+    let (start, end) = match PRESETS.get(period_idx) {
+        Some((_, start, end)) => (start.to_string(), end.to_string()),
+        None => {
+            let start = Input::<String>::new()
+                .with_prompt("Start date (YYYY-MM-DD)")
+                .interact_text()?;
+            let end = Input::<String>::new()
+                .with_prompt("End date (YYYY-MM-DD)")
+                .interact_text()?;
+            (start, end)
+        }
+    };
+
+    let rate_limit_ms = Input::<u64>::new()
+        .with_prompt("Rate limit between requests (ms)")
+        .default(DEFAULT_RATE_LIMIT_MS)
+        .interact_text()?;
+
+    let confirmed = Confirm::new()
+        .with_prompt(format!(
+            "Crawl zone {} from {} to {} with a {}ms rate limit?",
+            zone, start, end, rate_limit_ms
+        ))
+        .default(true)
+        .interact()?;
+
+    if !confirmed {
+        anyhow::bail!("Crawl cancelled");
+    }
+
+    Ok(Config {
+        zone,
+        start,
+        end,
+        max_pages: DEFAULT_MAX_PAGES,
+        rate_limit_ms,
+        sort: SortOrder::DateDesc,
+        output: OutputMode::Console,
+        output_path: None,
+        resume: None,
+    })
+}
@@ -1,80 +1,311 @@
+mod checkpoint;
+mod interactive;
+mod output;
+mod rate_limiter;
+
+use std::path::PathBuf;
+
 use anyhow::{Context, Result};
 use chrono::NaiveDate;
+use clap::Parser;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use checkpoint::Checkpoint;
+use output::OutputMode;
+use rate_limiter::RateLimiter;
 
 /// TBC Pre-patch period: May 18, 2021 - June 1, 2021
 const TBC_PREPATCH_START: &str = "2021-05-18";
 const TBC_PREPATCH_END: &str = "2021-06-01";
 
 const BASE_URL: &str = "https://classic.warcraftlogs.com/zone/reports";
-const ZONE_ID: u32 = 1006; // Naxxramas
+const SITE_ORIGIN: &str = "https://classic.warcraftlogs.com";
+const DEFAULT_ZONE_ID: u32 = 1006; // Naxxramas
+const DEFAULT_MAX_PAGES: usize = 50;
+const DEFAULT_RATE_LIMIT_MS: u64 = 500;
+
+/// Number of pages kept in flight concurrently while crawling.
+const FETCH_WINDOW: usize = 4;
+
+/// Search a WarcraftLogs zone's report history for logs in a date window.
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Config {
+    /// WarcraftLogs zone id to search (defaults to Naxxramas)
+    #[arg(long, default_value_t = DEFAULT_ZONE_ID)]
+    zone: u32,
+
+    /// Start of the date window, inclusive (YYYY-MM-DD)
+    #[arg(long, default_value = TBC_PREPATCH_START)]
+    start: String,
+
+    /// End of the date window, inclusive (YYYY-MM-DD)
+    #[arg(long, default_value = TBC_PREPATCH_END)]
+    end: String,
+
+    /// Stop crawling after this many pages even if more are available
+    #[arg(long, default_value_t = DEFAULT_MAX_PAGES)]
+    max_pages: usize,
+
+    /// Delay between page requests, in milliseconds
+    #[arg(long, default_value_t = DEFAULT_RATE_LIMIT_MS)]
+    rate_limit_ms: u64,
+
+    /// How to order the matched logs before printing
+    #[arg(long, value_enum, default_value_t = SortOrder::DateDesc)]
+    sort: SortOrder,
+
+    /// Where to send the matched logs
+    #[arg(long, value_enum, default_value_t = OutputMode::Console)]
+    output: OutputMode,
+
+    /// File path to write when --output is not `console`
+    #[arg(long)]
+    output_path: Option<PathBuf>,
+
+    /// Checkpoint file to resume a crawl from, and to keep updating as it progresses
+    #[arg(long)]
+    resume: Option<PathBuf>,
+}
+
+/// Ordering applied to the accumulated `LogEntry` list once crawling finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SortOrder {
+    /// Oldest log first
+    DateAsc,
+    /// Newest log first
+    DateDesc,
+    /// Keep the order logs were encountered in while crawling
+    None,
+}
+
+fn sort_logs(logs: &mut Vec<LogEntry>, order: SortOrder) {
+    match order {
+        SortOrder::DateAsc => logs.sort_by(|a, b| a.date.cmp(&b.date)),
+        SortOrder::DateDesc => logs.sort_by(|a, b| b.date.cmp(&a.date)),
+        SortOrder::None => {}
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // With no flags at all, prompt interactively instead of relying on defaults.
+    let config = if std::env::args_os().len() <= 1 {
+        interactive::prompt_config()?
+    } else {
+        Config::parse()
+    };
+
+    let start = NaiveDate::parse_from_str(&config.start, "%Y-%m-%d")
+        .with_context(|| format!("Invalid --start date: {}", config.start))?;
+    let end = NaiveDate::parse_from_str(&config.end, "%Y-%m-%d")
+        .with_context(|| format!("Invalid --end date: {}", config.end))?;
+
+    if start > end {
+        anyhow::bail!("--start ({}) must not be after --end ({})", start, end);
+    }
 
-fn main() -> Result<()> {
-    let prepatch_start = NaiveDate::parse_from_str(TBC_PREPATCH_START, "%Y-%m-%d")?;
-    let prepatch_end = NaiveDate::parse_from_str(TBC_PREPATCH_END, "%Y-%m-%d")?;
+    if config.output != OutputMode::Console && config.output_path.is_none() {
+        anyhow::bail!("--output-path is required when --output is not `console`");
+    }
 
-    println!("Searching for WoW Classic logs from TBC pre-patch period");
-    println!("Period: {} to {}", TBC_PREPATCH_START, TBC_PREPATCH_END);
+    println!("Searching zone {} for logs", config.zone);
+    println!("Period: {} to {}", start, end);
     println!();
 
-    let client = reqwest::blocking::Client::builder()
+    let client = reqwest::Client::builder()
         .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
         .build()?;
 
-    let mut page = 1;
-    loop {
-        let url = format!("{}?zone={}&page={}", BASE_URL, ZONE_ID, page);
-        println!("Fetching page {}...", page);
+    // Shared across the crawl and, if selected, the Markdown digest fetches
+    // below, so --rate-limit-ms bounds the *whole* run's request rate.
+    let limiter = RateLimiter::new(config.rate_limit_ms);
 
-        let response = client
-            .get(&url)
-            .send()
-            .context(format!("Failed to fetch page {}", page))?;
+    let mut matched_logs = crawl(&client, &config, &limiter, start, end).await?;
 
-        if !response.status().is_success() {
-            anyhow::bail!("HTTP error: {}", response.status());
-        }
+    sort_logs(&mut matched_logs, config.sort);
 
-        let html = response.text()?;
-        let document = Html::parse_document(&html);
+    if matched_logs.is_empty() {
+        println!("\nNo logs found from the requested period.");
+        return Ok(());
+    }
 
-        let (logs, oldest_date) = parse_logs(&document, prepatch_start, prepatch_end)?;
+    println!("\nFound {} logs from the requested period:", matched_logs.len());
 
-        if !logs.is_empty() {
-            println!("\nFound {} logs from TBC pre-patch period on page {}:", logs.len(), page);
-            for log in &logs {
+    match config.output {
+        OutputMode::Console => {
+            for log in &matched_logs {
                 println!("  - {} | {}", log.date, log.title);
             }
-            println!("\nFirst matching page: {}", page);
-            println!("URL: {}", url);
-            return Ok(());
         }
+        OutputMode::Ics => {
+            let path = config.output_path.as_deref().expect("validated above");
+            output::write_ics(&matched_logs, path)?;
+            println!("Wrote {} events to {}", matched_logs.len(), path.display());
+        }
+        OutputMode::Md => {
+            let path = config.output_path.as_deref().expect("validated above");
+            output::write_markdown_digest(&client, &limiter, &matched_logs, path).await?;
+            println!("Wrote digest for {} reports to {}", matched_logs.len(), path.display());
+        }
+    }
+
+    Ok(())
+}
 
-        // Check if we've gone past the prepatch period (logs are in reverse chronological order)
-        if let Some(oldest) = oldest_date {
-            if oldest < prepatch_start {
-                println!("\nReached logs older than pre-patch period. No logs found.");
-                return Ok(());
+/// Crawls pages starting from 1 (or from the saved page when `--resume`
+/// loads a checkpoint), keeping up to `FETCH_WINDOW` requests in
+/// flight at once while still processing their results in page order so the
+/// early-termination check below behaves exactly like the serial crawl did.
+async fn crawl(
+    client: &reqwest::Client,
+    config: &Config,
+    limiter: &RateLimiter,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<LogEntry>> {
+    let (mut matched_logs, mut next_page, mut running_oldest) = match &config.resume {
+        Some(path) => match Checkpoint::load(path)? {
+            Some(checkpoint) => {
+                checkpoint.ensure_matches(config.zone, start, end)?;
+                println!(
+                    "Resuming from checkpoint: page {}, {} logs matched so far",
+                    checkpoint.next_page,
+                    checkpoint.matched_logs.len()
+                );
+                (checkpoint.matched_logs, checkpoint.next_page, checkpoint.oldest_date)
             }
+            None => (Vec::new(), 1, None),
+        },
+        None => (Vec::new(), 1, None),
+    };
+    let mut done = false;
+
+    while !done {
+        let batch_start = next_page;
+        let batch_end = (batch_start + FETCH_WINDOW - 1).min(config.max_pages);
+        if batch_start > batch_end {
+            break;
+        }
+
+        let mut handles = Vec::new();
+        for page in batch_start..=batch_end {
+            limiter.acquire().await;
+            let client = client.clone();
+            let zone = config.zone;
+            handles.push((page, tokio::spawn(fetch_page(client, zone, page))));
         }
 
-        // Check if there are more pages
-        if !has_next_page(&document) {
-            println!("\nNo more pages. No logs found from pre-patch period.");
-            return Ok(());
+        for (page, handle) in handles {
+            if done {
+                // A page earlier in this batch already signalled the end of
+                // the window; cancel this still-outstanding request.
+                handle.abort();
+                continue;
+            }
+
+            let html = handle
+                .await
+                .context("fetch task panicked")?
+                .with_context(|| format!("Failed to fetch page {}", page))?;
+            let document = Html::parse_document(&html);
+
+            let (logs, oldest_date) = parse_logs(&document, start, end)?;
+            matched_logs.extend(logs);
+
+            if let Some(oldest) = oldest_date {
+                running_oldest = Some(running_oldest.map_or(oldest, |o: NaiveDate| o.min(oldest)));
+            }
+
+            if let Some(path) = &config.resume {
+                let checkpoint = Checkpoint {
+                    zone: config.zone,
+                    start,
+                    end,
+                    next_page: page + 1,
+                    matched_logs: matched_logs.clone(),
+                    oldest_date: running_oldest,
+                };
+                checkpoint.save(path)?;
+            }
+
+            // Logs are in reverse chronological order, so once a page's
+            // oldest entry predates the window there's nothing left to find.
+            if let Some(oldest) = oldest_date {
+                if oldest < start {
+                    done = true;
+                    continue;
+                }
+            }
+
+            if !has_next_page(&document) {
+                done = true;
+                continue;
+            }
+
+            if page >= config.max_pages {
+                println!("\nReached --max-pages ({}). Stopping.", config.max_pages);
+                done = true;
+                continue;
+            }
+
+            next_page = page + 1;
         }
+    }
+
+    Ok(matched_logs)
+}
+
+async fn fetch_page(client: reqwest::Client, zone: u32, page: usize) -> Result<String> {
+    let url = format!("{}?zone={}&page={}", BASE_URL, zone, page);
+    println!("Fetching page {}...", page);
+
+    let response = client.get(&url).send().await?;
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP error: {}", response.status());
+    }
 
-        page += 1;
+    Ok(response.text().await?)
+}
 
-        // Rate limiting
-        std::thread::sleep(std::time::Duration::from_millis(500));
+/// Fetches a single report's detail page, to be converted to Markdown for
+/// the `--output md` digest.
+pub(crate) async fn fetch_report_detail(client: &reqwest::Client, url: &str) -> Result<String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch report {}", url))?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP error: {}", response.status());
     }
+
+    Ok(response.text().await?)
+}
+
+/// Extracts the report's body from its detail page and converts it to Markdown.
+pub(crate) fn extract_report_body(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let body_selector =
+        Selector::parse("div.report-history, div.zone-performance, #report-content")
+            .expect("valid selector");
+
+    let body_html = document
+        .select(&body_selector)
+        .next()
+        .map(|el| el.inner_html())
+        .unwrap_or_default();
+
+    html2md::parse_html(&body_html)
 }
 
-#[derive(Debug)]
-struct LogEntry {
-    title: String,
-    date: NaiveDate,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct LogEntry {
+    pub(crate) title: String,
+    pub(crate) date: NaiveDate,
+    pub(crate) url: String,
 }
 
 fn parse_logs(
@@ -99,11 +330,11 @@ fn parse_logs(
 
     // First try table format
     for row in document.select(&row_selector) {
-        if let Some((date, title)) = extract_log_info(&row, &date_selector, &title_selector) {
+        if let Some((date, title, url)) = extract_log_info(&row, &date_selector, &title_selector) {
             oldest_date = Some(oldest_date.map_or(date, |d: NaiveDate| d.min(date)));
 
             if date >= start && date <= end {
-                matching_logs.push(LogEntry { title, date });
+                matching_logs.push(LogEntry { title, date, url });
             }
         }
     }
@@ -116,12 +347,16 @@ fn parse_logs(
                 oldest_date = Some(oldest_date.map_or(date, |d: NaiveDate| d.min(date)));
 
                 if date >= start && date <= end {
-                    let title = row
-                        .select(&Selector::parse("a").unwrap())
-                        .next()
+                    let link = row.select(&Selector::parse("a").unwrap()).next();
+                    let title = link
+                        .as_ref()
                         .map(|a| a.text().collect::<String>())
                         .unwrap_or_else(|| text.clone());
-                    matching_logs.push(LogEntry { title, date });
+                    let url = link
+                        .and_then(|a| a.value().attr("href"))
+                        .map(resolve_report_url)
+                        .unwrap_or_default();
+                    matching_logs.push(LogEntry { title, date, url });
                 }
             }
         }
@@ -134,15 +369,30 @@ fn extract_log_info(
     row: &scraper::ElementRef,
     date_sel: &Selector,
     title_sel: &Selector,
-) -> Option<(NaiveDate, String)> {
+) -> Option<(NaiveDate, String, String)> {
     let date_elem = row.select(date_sel).next()?;
     let date_text = date_elem.text().collect::<String>();
     let date = try_parse_date_from_text(&date_text)?;
 
     let title_elem = row.select(title_sel).next()?;
     let title = title_elem.text().collect::<String>();
+    let url = title_elem
+        .value()
+        .attr("href")
+        .map(resolve_report_url)
+        .unwrap_or_default();
+
+    Some((date, title.trim().to_string(), url))
+}
 
-    Some((date, title.trim().to_string()))
+/// Resolves a report link, which WarcraftLogs renders as a site-relative
+/// href, into the absolute URL `fetch_report_detail` can request.
+fn resolve_report_url(href: &str) -> String {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        href.to_string()
+    } else {
+        format!("{}{}", SITE_ORIGIN, href)
+    }
 }
 
 fn try_parse_date_from_text(text: &str) -> Option<NaiveDate> {
@@ -199,3 +449,53 @@ fn has_next_page(document: &Html) -> bool {
         .select(&next_selector)
         .any(|a| a.text().collect::<String>().to_lowercase().contains("next"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(title: &str, date: &str) -> LogEntry {
+        LogEntry {
+            title: title.to_string(),
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            url: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_sort_logs_date_asc() {
+        let mut logs = vec![
+            log("b", "2021-06-01"),
+            log("a", "2021-05-18"),
+            log("c", "2021-05-25"),
+        ];
+        sort_logs(&mut logs, SortOrder::DateAsc);
+        let titles: Vec<_> = logs.iter().map(|l| l.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_sort_logs_date_desc() {
+        let mut logs = vec![
+            log("a", "2021-05-18"),
+            log("b", "2021-06-01"),
+            log("c", "2021-05-25"),
+        ];
+        sort_logs(&mut logs, SortOrder::DateDesc);
+        let titles: Vec<_> = logs.iter().map(|l| l.title.as_str()).collect();
+        assert_eq!(titles, vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn test_sort_logs_none_is_noop() {
+        let mut logs = vec![
+            log("b", "2021-06-01"),
+            log("a", "2021-05-18"),
+            log("c", "2021-05-25"),
+        ];
+        let before: Vec<_> = logs.iter().map(|l| l.title.clone()).collect();
+        sort_logs(&mut logs, SortOrder::None);
+        let after: Vec<_> = logs.iter().map(|l| l.title.clone()).collect();
+        assert_eq!(before, after);
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use ics::properties::{DtStart, Summary};
+use ics::{Event, ICalendar};
+
+use crate::rate_limiter::RateLimiter;
+use crate::LogEntry;
+
+/// Output format selected via `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputMode {
+    /// Print matched logs to the console (default)
+    Console,
+    /// Write matched logs as an iCalendar (.ics) feed
+    Ics,
+    /// Fetch each report and write a Markdown digest
+    Md,
+}
+
+/// Writes `logs` as an iCalendar feed to `path`: one all-day VEVENT per log,
+/// with a UID derived from title+date so re-running the crawl is idempotent.
+pub fn write_ics(logs: &[LogEntry], path: &Path) -> Result<()> {
+    let mut calendar = ICalendar::new("2.0", "-//warcraftlogs-fetcher//EN");
+
+    for log in logs {
+        let dtstamp = log.date.format("%Y%m%dT000000Z").to_string();
+        let mut event = Event::new(event_uid(&log.title, log.date), dtstamp);
+
+        // Default VALUE for DTSTART is DATE-TIME; mark it DATE so this
+        // renders as the all-day event the log actually represents.
+        let mut dtstart = DtStart::new(log.date.format("%Y%m%d").to_string());
+        dtstart.add(("VALUE", "DATE"));
+        event.push(dtstart);
+
+        event.push(Summary::new(log.title.clone()));
+        calendar.add_event(event);
+    }
+
+    calendar
+        .save_file(path)
+        .with_context(|| format!("Failed to write ICS file to {}", path.display()))
+}
+
+/// Fetches every matched report and writes them to `path` as one Markdown
+/// document, each report as a heading with its date, a link back to the
+/// source URL, and its body converted from HTML to Markdown.
+///
+/// `limiter` is the same `RateLimiter` the crawl used, so these follow-up
+/// fetches stay within `--rate-limit-ms` too instead of hammering the site
+/// back-to-back once the page crawl has finished.
+pub async fn write_markdown_digest(
+    client: &reqwest::Client,
+    limiter: &RateLimiter,
+    logs: &[LogEntry],
+    path: &Path,
+) -> Result<()> {
+    let mut digest = String::new();
+
+    for log in logs {
+        digest.push_str(&format!("## {} — {}\n\n", log.date, log.title));
+        digest.push_str(&format!("[Source]({})\n\n", log.url));
+
+        limiter.acquire().await;
+        let html = crate::fetch_report_detail(client, &log.url).await?;
+        digest.push_str(&crate::extract_report_body(&html));
+        digest.push_str("\n\n");
+    }
+
+    fs::write(path, digest)
+        .with_context(|| format!("Failed to write Markdown digest to {}", path.display()))
+}
+
+/// Derives a stable UID from a log's title and date so that re-crawling the
+/// same report yields the same VEVENT UID instead of a new duplicate entry.
+///
+/// Uses a plain FNV-1a implementation rather than `DefaultHasher`: the
+/// standard library only guarantees `DefaultHasher`'s output is stable within
+/// a single build, not across toolchain versions, which would silently
+/// reshuffle every UID on rebuild.
+fn event_uid(title: &str, date: NaiveDate) -> String {
+    let digest = fnv1a(format!("{}|{}", title, date).as_bytes());
+    format!("{:016x}@warcraftlogs-fetcher", digest)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes
+        .iter()
+        .fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_uid_is_deterministic() {
+        let date = NaiveDate::parse_from_str("2021-05-18", "%Y-%m-%d").unwrap();
+        assert_eq!(event_uid("Naxxramas Clear", date), event_uid("Naxxramas Clear", date));
+    }
+
+    #[test]
+    fn test_event_uid_fixed_input_fixed_output() {
+        let date = NaiveDate::parse_from_str("2021-05-18", "%Y-%m-%d").unwrap();
+        assert_eq!(
+            event_uid("Naxxramas Clear", date),
+            "2aaba4a888e0c686@warcraftlogs-fetcher"
+        );
+    }
+
+    #[test]
+    fn test_event_uid_differs_by_title_or_date() {
+        let date = NaiveDate::parse_from_str("2021-05-18", "%Y-%m-%d").unwrap();
+        let other_date = NaiveDate::parse_from_str("2021-05-19", "%Y-%m-%d").unwrap();
+        assert_ne!(event_uid("Naxxramas Clear", date), event_uid("Other Report", date));
+        assert_ne!(event_uid("Naxxramas Clear", date), event_uid("Naxxramas Clear", other_date));
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// A global token-bucket limiter shared across concurrent fetch tasks so that
+/// `--rate-limit-ms` bounds the overall request rate rather than a per-task one.
+#[derive(Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    last_acquired: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(interval_ms: u64) -> Self {
+        let interval = Duration::from_millis(interval_ms);
+        let now = Instant::now();
+        Self {
+            interval,
+            // Back-date the first acquisition so the very first `acquire()`
+            // doesn't wait; `checked_sub` avoids panicking on a large
+            // `--rate-limit-ms` or a clock too close to process start.
+            last_acquired: Arc::new(Mutex::new(now.checked_sub(interval).unwrap_or(now))),
+        }
+    }
+
+    /// Blocks the caller until at least `interval` has elapsed since the last
+    /// acquisition by any clone of this limiter.
+    pub async fn acquire(&self) {
+        let mut last = self.last_acquired.lock().await;
+        let earliest_next = *last + self.interval;
+        let now = Instant::now();
+        if earliest_next > now {
+            tokio::time::sleep(earliest_next - now).await;
+        }
+        *last = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_spaces_calls_by_interval() {
+        let limiter = RateLimiter::new(50);
+
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(45), "elapsed was {:?}", elapsed);
+    }
+
+    #[test]
+    fn test_new_does_not_panic_on_large_rate_limit() {
+        // Larger than any plausible process uptime; must not underflow `Instant`.
+        let _limiter = RateLimiter::new(u64::MAX / 2);
+    }
+}